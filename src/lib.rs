@@ -105,7 +105,7 @@ pub use crate::{
     color::{ByColor, ByColorIter, Color, ParseColorError},
     material::{Material, MaterialSide, ParseMaterialError},
     movelist::MoveList,
-    perft::perft,
+    perft::{perft, perft_divide, perft_parallel, perft_with_cache, PerftCache},
     position::{Chess, FromSetup, Outcome, PlayError, Position, PositionError, PositionErrorKinds},
     setup::{Castles, Setup},
     square::{File, ParseSquareError, Rank, Square},
@@ -14,7 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use crate::position::Position;
+use crate::{
+    position::{EnPassantMode, Position},
+    types::Move,
+    zobrist::{Zobrist64, ZobristHash},
+};
 
 /// Counts legal move paths of a given length.
 ///
@@ -45,6 +49,10 @@ pub fn perft<P: Position + Clone>(pos: &P, depth: u32) -> u64 {
     if depth < 1 {
         1
     } else {
+        // A non-allocating `Position::count_legal_moves` leaf would avoid
+        // materializing a MoveList here, but that requires a counting sink
+        // in the move generator (board.rs/position.rs), which is out of
+        // scope for this change. Enumerate and count instead.
         let moves = pos.legal_moves();
 
         if depth == 1 {
@@ -62,6 +70,195 @@ pub fn perft<P: Position + Clone>(pos: &P, depth: u32) -> u64 {
     }
 }
 
+/// Below this depth, the overhead of spawning worker threads outweighs any
+/// gains from [`perft_parallel`], so it falls back to sequential [`perft`].
+const PARALLEL_THRESHOLD: u32 = 4;
+
+/// Computes [`perft`] numbers using a bounded pool of worker threads.
+///
+/// The root's legal moves are distributed across up to `threads` workers,
+/// each sequentially enumerating the `depth - 1` subtree of one or more root
+/// moves. `threads == 0` is treated the same as `threads == 1`.
+///
+/// Below a depth of 4, spawning threads does not pay for itself, so this
+/// falls back to the sequential [`perft`].
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, perft_parallel};
+///
+/// let pos = Chess::default();
+/// assert_eq!(perft_parallel(&pos, 4, 4), 197281);
+/// ```
+pub fn perft_parallel<P>(pos: &P, depth: u32, threads: usize) -> u64
+where
+    P: Position + Clone + Send,
+{
+    if depth < PARALLEL_THRESHOLD {
+        return perft(pos, depth);
+    }
+
+    let children: Vec<P> = pos
+        .legal_moves()
+        .iter()
+        .map(|m| {
+            let mut child = pos.clone();
+            child.play_unchecked(m);
+            child
+        })
+        .collect();
+
+    let threads = threads.max(1).min(children.len().max(1));
+    let next_depth = depth - 1;
+    let chunk_size = children.len().div_ceil(threads).max(1);
+
+    std::thread::scope(|scope| {
+        children
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| chunk.iter().map(|child| perft(child, next_depth)).sum::<u64>()))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().expect("perft worker thread panicked"))
+            .sum()
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PerftCacheEntry {
+    key: u64,
+    depth: u8,
+    count: u64,
+}
+
+/// A fixed-capacity transposition table for memoizing [`perft_with_cache`]
+/// subtree counts.
+///
+/// Entries are indexed by `zobrist_key % capacity` and overwritten using an
+/// always-replace policy, so a larger capacity means fewer evictions (and
+/// fewer false misses) at the cost of more memory.
+#[derive(Debug, Clone)]
+pub struct PerftCache {
+    entries: Vec<Option<PerftCacheEntry>>,
+}
+
+impl PerftCache {
+    /// Creates a cache with room for `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is `0`.
+    pub fn with_capacity(capacity: usize) -> PerftCache {
+        assert!(capacity > 0, "capacity must be greater than 0");
+        PerftCache {
+            entries: vec![None; capacity],
+        }
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+
+    fn get(&self, key: u64, depth: u8) -> Option<u64> {
+        self.entries[self.slot(key)]
+            .filter(|entry| entry.key == key && entry.depth == depth)
+            .map(|entry| entry.count)
+    }
+
+    fn insert(&mut self, key: u64, depth: u8, count: u64) {
+        let slot = self.slot(key);
+        self.entries[slot] = Some(PerftCacheEntry { key, depth, count });
+    }
+}
+
+/// Computes [`perft`] numbers, memoizing subtree counts in a fixed-capacity
+/// transposition `cache` keyed on the position's zobrist hash.
+///
+/// This can dramatically speed up deep perft computations where
+/// transpositions (the same position reached by different move orders) are
+/// common. Entries for `depth <= 1` are never cached, since recomputing them
+/// is cheaper than a cache probe. A cache hit always verifies the full stored
+/// key to guard against index collisions, since the hash is truncated to
+/// find the slot.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, PerftCache, perft_with_cache};
+///
+/// let pos = Chess::default();
+/// let mut cache = PerftCache::with_capacity(1 << 16);
+/// assert_eq!(perft_with_cache(&pos, 4, &mut cache), 197281);
+/// ```
+pub fn perft_with_cache<P: Position + Clone>(pos: &P, depth: u32, cache: &mut PerftCache) -> u64 {
+    if depth < 1 {
+        return 1;
+    }
+
+    let key = pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal).0;
+
+    if depth >= 2 {
+        if let Some(count) = cache.get(key, depth as u8) {
+            return count;
+        }
+    }
+
+    // See the comment in `perft`: a non-allocating leaf count is not
+    // available without extending the move generator, so enumerate here too.
+    let moves = pos.legal_moves();
+
+    let count = if depth == 1 {
+        moves.len() as u64
+    } else {
+        moves
+            .iter()
+            .map(|m| {
+                let mut child = pos.clone();
+                child.play_unchecked(m);
+                perft_with_cache(&child, depth - 1, cache)
+            })
+            .sum()
+    };
+
+    if depth >= 2 {
+        cache.insert(key, depth as u8, count);
+    }
+
+    count
+}
+
+/// Computes a [`perft`] count for each of the root's legal moves individually.
+///
+/// This is the standard tool for bisecting move generation discrepancies
+/// against a reference engine: compare the per-root-move subtree counts to
+/// pinpoint exactly which move's generation diverges. Moves are returned in
+/// the same order as [`Position::legal_moves`], which is not guaranteed to
+/// be stable across crate versions but is deterministic for a given version.
+///
+/// Combine with [`san::San`](crate::san::San) or
+/// [`uci::Uci`](crate::uci::Uci) to print `e2e4: 20` style breakdowns.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, perft_divide};
+///
+/// let pos = Chess::default();
+/// let divided = perft_divide(&pos, 2);
+/// assert_eq!(divided.len(), 20);
+/// assert_eq!(divided.iter().map(|(_, count)| count).sum::<u64>(), 400);
+/// ```
+pub fn perft_divide<P: Position + Clone>(pos: &P, depth: u32) -> Vec<(Move, u64)> {
+    pos.legal_moves()
+        .iter()
+        .map(|m| {
+            let mut child = pos.clone();
+            child.play_unchecked(m);
+            (m.clone(), perft(&child, depth.saturating_sub(1)))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,4 +270,28 @@ mod tests {
         assert_eq!(perft(&pos, 0), 1);
         assert_eq!(perft(&pos, 1), 20);
     }
+
+    #[test]
+    fn test_perft_parallel() {
+        let pos = Chess::default();
+        assert_eq!(perft_parallel(&pos, 4, 1), perft(&pos, 4));
+        assert_eq!(perft_parallel(&pos, 4, 4), perft(&pos, 4));
+    }
+
+    #[test]
+    fn test_perft_with_cache() {
+        let pos = Chess::default();
+        let mut cache = PerftCache::with_capacity(1 << 10);
+        assert_eq!(perft_with_cache(&pos, 4, &mut cache), perft(&pos, 4));
+        // Second call exercises the warmed cache.
+        assert_eq!(perft_with_cache(&pos, 4, &mut cache), perft(&pos, 4));
+    }
+
+    #[test]
+    fn test_perft_divide() {
+        let pos = Chess::default();
+        let divided = perft_divide(&pos, 3);
+        assert_eq!(divided.len(), 20);
+        assert_eq!(divided.iter().map(|(_, count)| count).sum::<u64>(), perft(&pos, 3));
+    }
 }